@@ -0,0 +1,121 @@
+use std::process::Command;
+
+/// Defaults read from the `[delta]` section of the user's git config
+/// (`git config --get-regexp '^delta\.'`). Each field is `None` when the
+/// corresponding key is absent, so callers can layer these in beneath
+/// command-line flags and above the built-in defaults.
+#[derive(Default)]
+pub struct GitConfig {
+    pub theme: Option<String>,
+    pub plus_color: Option<String>,
+    pub minus_color: Option<String>,
+    pub width: Option<usize>,
+    pub light: Option<bool>,
+    pub dark: Option<bool>,
+}
+
+/// Read `[delta]` settings from git config. Returns all-`None` defaults
+/// if git is not installed, there is no config, or it cannot be parsed.
+pub fn read() -> GitConfig {
+    let output = match Command::new("git")
+        .args(&["config", "--get-regexp", r"^delta\."])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return GitConfig::default(),
+    };
+    parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the output of `git config --get-regexp '^delta\.'`, one `key
+/// value` pair per line (a valueless boolean entry, e.g. a bare `light`
+/// line, has an empty value).
+fn parse(output: &str) -> GitConfig {
+    let mut config = GitConfig::default();
+    for line in output.lines() {
+        let mut parts = line.splitn(2, ' ');
+        let key = match parts.next() {
+            Some(key) => key,
+            None => continue,
+        };
+        let value = parts.next().unwrap_or("");
+        match key {
+            "delta.theme" => config.theme = Some(value.to_string()),
+            "delta.plus-color" => config.plus_color = Some(value.to_string()),
+            "delta.minus-color" => config.minus_color = Some(value.to_string()),
+            "delta.width" => config.width = value.parse().ok(),
+            "delta.light" => config.light = parse_git_bool(value),
+            "delta.dark" => config.dark = parse_git_bool(value),
+            _ => (),
+        }
+    }
+    config
+}
+
+/// Parse a git config boolean value using git's own truthy/falsy set
+/// (`true`/`yes`/`on`/`1` and `false`/`no`/`off`/`0`, case-insensitively),
+/// rather than Rust's `str::parse::<bool>()`, which only accepts the
+/// literal strings `"true"`/`"false"`. A valueless entry (an empty
+/// string, as `--get-regexp` reports for a bare `light`/`dark` line) is
+/// also treated as `true`, matching git's own semantics for boolean
+/// config variables.
+fn parse_git_bool(value: &str) -> Option<bool> {
+    if value.is_empty() {
+        return Some(true);
+    }
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_string_and_numeric_fields() {
+        let config = parse("delta.theme Solarized (dark)\ndelta.width 120\n");
+        assert_eq!(config.theme, Some("Solarized (dark)".to_string()));
+        assert_eq!(config.width, Some(120));
+    }
+
+    #[test]
+    fn parses_bare_boolean_entry_as_true() {
+        // `git config --get-regexp` reports a valueless boolean entry
+        // (e.g. a bare `light` line in .gitconfig) with an empty value.
+        assert_eq!(parse("delta.light \n").light, Some(true));
+    }
+
+    #[test]
+    fn parses_gits_full_boolean_truthy_falsy_set() {
+        for truthy in &["true", "yes", "on", "1", "TRUE"] {
+            assert_eq!(parse_git_bool(truthy), Some(true), "{}", truthy);
+        }
+        for falsy in &["false", "no", "off", "0", "FALSE"] {
+            assert_eq!(parse_git_bool(falsy), Some(false), "{}", falsy);
+        }
+    }
+
+    #[test]
+    fn rejects_unrecognized_boolean_value() {
+        assert_eq!(parse_git_bool("maybe"), None);
+    }
+
+    #[test]
+    fn unknown_keys_and_missing_entries_are_ignored() {
+        let config = parse("delta.unknown-key something\n");
+        assert_eq!(config.theme, None);
+        assert_eq!(config.light, None);
+    }
+
+    #[test]
+    fn empty_output_yields_all_defaults() {
+        let config = parse("");
+        assert_eq!(config.theme, None);
+        assert_eq!(config.width, None);
+        assert_eq!(config.light, None);
+        assert_eq!(config.dark, None);
+    }
+}
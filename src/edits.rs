@@ -0,0 +1,128 @@
+/// Given a line that was removed (`minus_line`) and the line that
+/// replaced it (`plus_line`) — each including its leading `-`/`+` marker
+/// — compute the byte ranges within each line that are *not* part of
+/// their longest common character subsequence, i.e. the regions that
+/// actually changed. The marker itself is excluded from the comparison
+/// (the minus side's `-` and the plus side's `+` would otherwise never
+/// match each other, spuriously flagging every paired line's first
+/// character as changed) and ranges are returned relative to the full,
+/// marker-included line, matching what callers pass in.
+pub fn infer_edits(minus_line: &str, plus_line: &str) -> (Vec<(usize, usize)>, Vec<(usize, usize)>) {
+    let marker_len = 1;
+    let minus_body = minus_line.get(marker_len..).unwrap_or("");
+    let plus_body = plus_line.get(marker_len..).unwrap_or("");
+    let minus_chars: Vec<char> = minus_body.chars().collect();
+    let plus_chars: Vec<char> = plus_body.chars().collect();
+    let (minus_common, plus_common) = lcs_mask(&minus_chars, &plus_chars);
+    (
+        offset_ranges(ranges_of_false(&minus_common, &minus_chars), marker_len),
+        offset_ranges(ranges_of_false(&plus_common, &plus_chars), marker_len),
+    )
+}
+
+/// Shift each range by `offset` bytes, to translate ranges computed over
+/// a line's body back into the full line's byte coordinates.
+fn offset_ranges(ranges: Vec<(usize, usize)>, offset: usize) -> Vec<(usize, usize)> {
+    ranges.into_iter().map(|(start, end)| (start + offset, end + offset)).collect()
+}
+
+/// Return, for each side, a boolean mask indicating which characters
+/// participate in the longest common subsequence of `a` and `b`.
+fn lcs_mask(a: &[char], b: &[char]) -> (Vec<bool>, Vec<bool>) {
+    let n = a.len();
+    let m = b.len();
+    let mut dp = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            dp[i][j] = if a[i] == b[j] {
+                dp[i + 1][j + 1] + 1
+            } else {
+                dp[i + 1][j].max(dp[i][j + 1])
+            };
+        }
+    }
+    let mut a_mask = vec![false; n];
+    let mut b_mask = vec![false; m];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            a_mask[i] = true;
+            b_mask[j] = true;
+            i += 1;
+            j += 1;
+        } else if dp[i + 1][j] >= dp[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (a_mask, b_mask)
+}
+
+/// Convert a per-character "is common" mask into the byte ranges of the
+/// runs where it is false, i.e. the edited regions.
+fn ranges_of_false(mask: &[bool], chars: &[char]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut byte_offset = 0;
+    let mut run_start: Option<usize> = None;
+    for (is_common, ch) in mask.iter().zip(chars.iter()) {
+        if !is_common {
+            if run_start.is_none() {
+                run_start = Some(byte_offset);
+            }
+        } else if let Some(start) = run_start.take() {
+            ranges.push((start, byte_offset));
+        }
+        byte_offset += ch.len_utf8();
+    }
+    if let Some(start) = run_start {
+        ranges.push((start, byte_offset));
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_lines_have_no_edits() {
+        assert_eq!(infer_edits("-foo bar", "+foo bar"), (vec![], vec![]));
+    }
+
+    #[test]
+    fn single_character_change_is_isolated() {
+        let (minus, plus) = infer_edits("-cat", "+car");
+        assert_eq!(minus, vec![(3, 4)]);
+        assert_eq!(plus, vec![(3, 4)]);
+    }
+
+    #[test]
+    fn wholly_different_lines_mark_the_whole_line() {
+        let (minus, plus) = infer_edits("-abc", "+xyz");
+        assert_eq!(minus, vec![(1, 4)]);
+        assert_eq!(plus, vec![(1, 4)]);
+    }
+
+    #[test]
+    fn multibyte_characters_use_byte_offsets() {
+        // The common "caf" prefix is 3 bytes on both sides (after the
+        // 1-byte marker), but the differing last character is 2 bytes on
+        // the minus side ('é') and 1 byte on the plus side ('e').
+        let (minus, plus) = infer_edits("-café", "+cafe");
+        assert_eq!(minus, vec![(4, 6)]);
+        assert_eq!(plus, vec![(4, 5)]);
+    }
+
+    #[test]
+    fn empty_lines_produce_no_edits() {
+        assert_eq!(infer_edits("-", "+"), (vec![], vec![]));
+    }
+
+    #[test]
+    fn only_the_content_is_compared_not_the_marker() {
+        // The minus/plus markers themselves always differ ('-' vs '+'),
+        // but identical content after them must not be flagged as edited.
+        assert_eq!(infer_edits("-unchanged()", "+unchanged()"), (vec![], vec![]));
+    }
+}
@@ -1,7 +1,10 @@
 extern crate structopt;
 
+mod config;
+mod edits;
 mod paint;
 mod parse_diff;
+mod terminal;
 
 use std::io::{self, BufRead, ErrorKind, Write};
 use std::process;
@@ -17,12 +20,14 @@ use syntect::parsing::SyntaxReference;
             about = "A syntax-highlighter for git. Use 'delta | less -R' as core.pager in .gitconfig")]
 struct Opt {
     /// Use diff highlighting colors appropriate for a light terminal
-    /// background. This is the default.
+    /// background. If neither this nor --dark is given, the terminal's
+    /// background is detected automatically, falling back to light.
     #[structopt(long = "light")]
     light: bool,
 
     /// Use diff highlighting colors appropriate for a dark terminal
-    /// background.
+    /// background. If neither this nor --light is given, the terminal's
+    /// background is detected automatically.
     #[structopt(long = "dark")]
     dark: bool,
 
@@ -50,6 +55,18 @@ struct Opt {
     /// each line
     #[structopt(short = "w", long = "width")]
     width: Option<usize>,
+
+    /// Display diffs in two columns, with the old version of a hunk
+    /// on the left and the new version on the right, instead of the
+    /// default inline stream. Combine with --width to control the
+    /// width of each column.
+    #[structopt(long = "side-by-side")]
+    side_by_side: bool,
+
+    /// Prefix each hunk line with a gutter showing its old and new
+    /// line numbers.
+    #[structopt(long = "line-numbers")]
+    line_numbers: bool,
 }
 
 #[derive(PartialEq)]
@@ -72,8 +89,19 @@ fn main() {
     }
 }
 
+/// A line buffered from a minus- or plus-run, together with its old
+/// and/or new line number (whichever side it belongs to), for later use
+/// by the `--line-numbers` gutter.
+struct BufferedLine {
+    text: String,
+    old_line_number: Option<usize>,
+    new_line_number: Option<usize>,
+}
+
 fn delta() -> std::io::Result<()> {
     let mut opt = Opt::from_args();
+    let side_by_side = opt.side_by_side;
+    let line_numbers = opt.line_numbers;
     let theme_set = ThemeSet::load_defaults();
     let paint_config = parse_args(&theme_set, &mut opt);
 
@@ -84,29 +112,126 @@ fn delta() -> std::io::Result<()> {
     let mut output = String::new();
     let mut state = State::Unknown;
     let mut did_emit_line: bool;
+    let mut minus_buffer: Vec<BufferedLine> = Vec::new();
+    let mut plus_buffer: Vec<BufferedLine> = Vec::new();
+    let mut old_line_number = 0;
+    let mut new_line_number = 0;
+    let mut awaiting_shebang_detection = false;
 
     for _line in stdin.lock().lines() {
         let raw_line = _line?;
         let line: String = strip_ansi_codes(&raw_line).to_string();
         did_emit_line = false;
         if line.starts_with("diff --") {
+            flush_hunk_buffer(
+                &mut minus_buffer,
+                &mut plus_buffer,
+                syntax,
+                &paint_config,
+                side_by_side,
+                line_numbers,
+                &mut stdout,
+            )?;
             state = State::DiffMeta;
-            syntax = match parse_diff::get_file_extension_from_diff_line(&line) {
-                Some(extension) => paint_config.syntax_set.find_syntax_by_extension(extension),
-                None => None,
-            };
+            syntax = parse_diff::get_file_extension_from_diff_line(&line)
+                .and_then(|extension| paint_config.syntax_set.find_syntax_by_extension(extension))
+                .or_else(|| {
+                    parse_diff::get_file_name_from_diff_line(&line).and_then(|file_name| {
+                        paint_config
+                            .syntax_set
+                            .find_syntax_by_extension(file_name)
+                            .or_else(|| paint_config.syntax_set.find_syntax_by_name(file_name))
+                            .or_else(|| {
+                                parse_diff::lookup_known_extensionless_file(file_name)
+                                    .and_then(|extension| paint_config.syntax_set.find_syntax_by_extension(extension))
+                            })
+                    })
+                });
+            awaiting_shebang_detection = syntax.is_none();
         } else if line.starts_with("commit") {
+            flush_hunk_buffer(
+                &mut minus_buffer,
+                &mut plus_buffer,
+                syntax,
+                &paint_config,
+                side_by_side,
+                line_numbers,
+                &mut stdout,
+            )?;
             state = State::Commit;
         } else if line.starts_with("@@") {
+            flush_hunk_buffer(
+                &mut minus_buffer,
+                &mut plus_buffer,
+                syntax,
+                &paint_config,
+                side_by_side,
+                line_numbers,
+                &mut stdout,
+            )?;
             state = State::DiffHunk;
+            if let Some((old_start, new_start)) = parse_diff::parse_hunk_header(&line) {
+                old_line_number = old_start;
+                new_line_number = new_start;
+            }
         } else if state == State::DiffHunk {
-            match syntax {
-                Some(syntax) => {
-                    paint::paint_line(line, syntax, &paint_config, &mut output);
-                    writeln!(stdout, "{}", output)?;
-                    output.truncate(0);
-                    did_emit_line = true;
+            if awaiting_shebang_detection {
+                awaiting_shebang_detection = false;
+                let content = line.get(1..).unwrap_or("");
+                if content.starts_with("#!") {
+                    syntax = paint_config.syntax_set.find_syntax_by_first_line(content);
                 }
+            }
+            match syntax {
+                Some(syntax) => match line.chars().next() {
+                    Some('-') => {
+                        minus_buffer.push(BufferedLine {
+                            text: line,
+                            old_line_number: Some(old_line_number),
+                            new_line_number: None,
+                        });
+                        old_line_number += 1;
+                        did_emit_line = true;
+                    }
+                    Some('+') => {
+                        plus_buffer.push(BufferedLine {
+                            text: line,
+                            old_line_number: None,
+                            new_line_number: Some(new_line_number),
+                        });
+                        new_line_number += 1;
+                        did_emit_line = true;
+                    }
+                    _ => {
+                        flush_hunk_buffer(
+                            &mut minus_buffer,
+                            &mut plus_buffer,
+                            Some(syntax),
+                            &paint_config,
+                            side_by_side,
+                            line_numbers,
+                            &mut stdout,
+                        )?;
+                        let is_context = line.starts_with(' ');
+                        let gutter = if line_numbers && is_context {
+                            Some((Some(old_line_number), Some(new_line_number)))
+                        } else {
+                            None
+                        };
+                        paint::paint_line(line, syntax, &paint_config, gutter, &mut output);
+                        if side_by_side {
+                            writeln!(stdout, "{} │ {}", output, output)?;
+                        } else {
+                            writeln!(stdout, "{}", output)?;
+                        }
+                        output.truncate(0);
+                        if is_context {
+                            old_line_number += 1;
+                            new_line_number += 1;
+                        }
+                        did_emit_line = true;
+                    }
+                },
                 None => (),
             }
         }
@@ -114,10 +239,143 @@ fn delta() -> std::io::Result<()> {
             writeln!(stdout, "{}", raw_line)?;
         }
     }
+    flush_hunk_buffer(
+        &mut minus_buffer,
+        &mut plus_buffer,
+        syntax,
+        &paint_config,
+        side_by_side,
+        line_numbers,
+        &mut stdout,
+    )?;
     Ok(())
 }
 
+/// Pair up the buffered minus-run and plus-run lines of a hunk. Each
+/// aligned pair gets its changed regions highlighted via
+/// `edits::infer_edits`/`paint::paint_line_with_emphasis`; unpaired
+/// leftovers fall back to plain `paint::paint_line`. Writes the result
+/// either as two side-by-side columns or as a plain inline stream,
+/// depending on `side_by_side`. No-op if both buffers are empty.
+fn flush_hunk_buffer(
+    minus_buffer: &mut Vec<BufferedLine>,
+    plus_buffer: &mut Vec<BufferedLine>,
+    syntax: Option<&SyntaxReference>,
+    paint_config: &paint::Config,
+    side_by_side: bool,
+    line_numbers: bool,
+    stdout: &mut impl Write,
+) -> std::io::Result<()> {
+    if minus_buffer.is_empty() && plus_buffer.is_empty() {
+        return Ok(());
+    }
+    // Painted rows are padded to `config.width` characters in total
+    // (gutter included, per `paint::pad_to_width`; longer rows are not
+    // truncated), so the filler for an unpaired side-by-side row is that
+    // same width.
+    let blank_column = match paint_config.width {
+        Some(width) => " ".repeat(width),
+        None => String::new(),
+    };
+    let n_rows = minus_buffer.len().max(plus_buffer.len());
+    let mut minus_outputs: Vec<String> = Vec::with_capacity(minus_buffer.len());
+    let mut plus_outputs: Vec<String> = Vec::with_capacity(plus_buffer.len());
+    for i in 0..n_rows {
+        let (minus_emphasis, plus_emphasis) = match (minus_buffer.get(i), plus_buffer.get(i)) {
+            (Some(minus_line), Some(plus_line)) => {
+                let (m, p) = edits::infer_edits(&minus_line.text, &plus_line.text);
+                (Some(m), Some(p))
+            }
+            _ => (None, None),
+        };
+        if let (Some(buffered), Some(syntax)) = (minus_buffer.get(i), syntax) {
+            let gutter = if line_numbers {
+                Some((buffered.old_line_number, buffered.new_line_number))
+            } else {
+                None
+            };
+            let mut painted = String::new();
+            match &minus_emphasis {
+                Some(spans) => paint::paint_line_with_emphasis(
+                    buffered.text.clone(),
+                    syntax,
+                    paint_config,
+                    spans,
+                    gutter,
+                    &mut painted,
+                ),
+                None => paint::paint_line(buffered.text.clone(), syntax, paint_config, gutter, &mut painted),
+            }
+            minus_outputs.push(painted);
+        }
+        if let (Some(buffered), Some(syntax)) = (plus_buffer.get(i), syntax) {
+            let gutter = if line_numbers {
+                Some((buffered.old_line_number, buffered.new_line_number))
+            } else {
+                None
+            };
+            let mut painted = String::new();
+            match &plus_emphasis {
+                Some(spans) => paint::paint_line_with_emphasis(
+                    buffered.text.clone(),
+                    syntax,
+                    paint_config,
+                    spans,
+                    gutter,
+                    &mut painted,
+                ),
+                None => paint::paint_line(buffered.text.clone(), syntax, paint_config, gutter, &mut painted),
+            }
+            plus_outputs.push(painted);
+        }
+    }
+    if side_by_side {
+        for i in 0..n_rows {
+            let left = minus_outputs.get(i).map(String::as_str).unwrap_or(&blank_column);
+            let right = plus_outputs.get(i).map(String::as_str).unwrap_or(&blank_column);
+            writeln!(stdout, "{} │ {}", left, right)?;
+        }
+    } else {
+        for line in &minus_outputs {
+            writeln!(stdout, "{}", line)?;
+        }
+        for line in &plus_outputs {
+            writeln!(stdout, "{}", line)?;
+        }
+    }
+    minus_buffer.clear();
+    plus_buffer.clear();
+    Ok(())
+}
+
+/// Fill in any `Opt` fields the user did not pass on the command line
+/// from the `[delta]` section of git config, giving command-line flags
+/// precedence over config, and config precedence over the built-in
+/// defaults applied later in `parse_args`.
+fn apply_git_config_defaults(opt: &mut Opt, git_config: &config::GitConfig) {
+    if !opt.light && !opt.dark {
+        if let Some(true) = git_config.dark {
+            opt.dark = true;
+        } else if let Some(true) = git_config.light {
+            opt.light = true;
+        }
+    }
+    if opt.theme.is_none() {
+        opt.theme = git_config.theme.clone();
+    }
+    if opt.plus_color.is_none() {
+        opt.plus_color = git_config.plus_color.clone();
+    }
+    if opt.minus_color.is_none() {
+        opt.minus_color = git_config.minus_color.clone();
+    }
+    if opt.width.is_none() {
+        opt.width = git_config.width;
+    }
+}
+
 fn parse_args<'a>(theme_set: &'a ThemeSet, opt: &'a mut Opt) -> paint::Config<'a> {
+    apply_git_config_defaults(opt, &config::read());
 
     if opt.light && opt.dark {
         eprintln!("--light or --dark cannot be used together. Default is --light.");
@@ -133,7 +391,11 @@ fn parse_args<'a>(theme_set: &'a ThemeSet, opt: &'a mut Opt) -> paint::Config<'a
         }
         None => {
             if !(opt.light || opt.dark) {
-                opt.light = true;
+                match terminal::detect_background() {
+                    Some(terminal::Background::Dark) => opt.dark = true,
+                    Some(terminal::Background::Light) => opt.light = true,
+                    None => opt.light = true,
+                }
             }
             match opt.light {
                 true => "InspiredGitHub",
@@ -153,5 +415,6 @@ fn parse_args<'a>(theme_set: &'a ThemeSet, opt: &'a mut Opt) -> paint::Config<'a
         plus_color,
         minus_color,
         opt.width,
+        terminal::supports_truecolor(),
     )
 }
@@ -0,0 +1,313 @@
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, FontStyle, Style, Theme};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::as_24_bit_terminal_escaped;
+
+pub struct Config<'a> {
+    pub theme: &'a Theme,
+    pub theme_name: &'a str,
+    pub plus_color: Option<Color>,
+    pub minus_color: Option<Color>,
+    pub width: Option<usize>,
+    pub true_color: bool,
+    pub syntax_set: SyntaxSet,
+}
+
+pub fn get_config<'a>(
+    theme: &'a Theme,
+    theme_name: &'a str,
+    plus_color: Option<Color>,
+    minus_color: Option<Color>,
+    width: Option<usize>,
+    true_color: bool,
+) -> Config<'a> {
+    let is_light_theme = ["InspiredGitHub", "Solarized (light)", "base16-ocean.light"].contains(&theme_name);
+    let rgb = |r: u8, g: u8, b: u8| Color { r, g, b, a: 0xff };
+    let plus_default = if is_light_theme { rgb(0xd0, 0xff, 0xd0) } else { rgb(0x01, 0x3b, 0x01) };
+    let minus_default = if is_light_theme { rgb(0xff, 0xd0, 0xd0) } else { rgb(0x3f, 0x00, 0x01) };
+    Config {
+        theme,
+        theme_name,
+        plus_color: plus_color.or(Some(plus_default)),
+        minus_color: minus_color.or(Some(minus_default)),
+        width,
+        true_color,
+        syntax_set: SyntaxSet::load_defaults_newlines(),
+    }
+}
+
+/// Syntax-highlight `line` and write the result, with an added/removed
+/// background color applied, into `output`. If `gutter` is given, a dim
+/// `(old line number, new line number)` column is written first.
+pub fn paint_line(
+    line: String,
+    syntax: &SyntaxReference,
+    config: &Config,
+    gutter: Option<(Option<usize>, Option<usize>)>,
+    output: &mut String,
+) {
+    if let Some((old, new)) = gutter {
+        output.push_str(&render_gutter(old, new));
+    }
+    let background_color = match line.chars().next() {
+        Some('+') => config.plus_color,
+        Some('-') => config.minus_color,
+        _ => None,
+    };
+    let mut highlighter = HighlightLines::new(syntax, config.theme);
+    let mut ranges: Vec<(Style, &str)> = highlighter.highlight(&line, &config.syntax_set);
+    if let Some(background_color) = background_color {
+        for (style, _) in ranges.iter_mut() {
+            style.background = background_color;
+        }
+    }
+    output.push_str(&escape_ranges(&ranges, config));
+    pad_to_width(
+        line.chars().count(),
+        ranges.last().map(|(style, _)| *style),
+        config,
+        output,
+        gutter_prefix_width(gutter),
+    );
+}
+
+/// Like `paint_line`, but additionally overlays an emphasis style on the
+/// byte ranges in `emphasized`, which should be the edited regions
+/// computed by `edits::infer_edits` for the minus/plus line this is
+/// paired with. Emphasized ranges are bolded and have their background
+/// brightened; the unemphasized parts of the line are dimmed towards the
+/// background instead, so the emphasis is visible purely from the line's
+/// own syntax-highlighting colors, whether or not `--plus-color`/
+/// `--minus-color` were passed.
+pub fn paint_line_with_emphasis(
+    line: String,
+    syntax: &SyntaxReference,
+    config: &Config,
+    emphasized: &[(usize, usize)],
+    gutter: Option<(Option<usize>, Option<usize>)>,
+    output: &mut String,
+) {
+    if let Some((old, new)) = gutter {
+        output.push_str(&render_gutter(old, new));
+    }
+    let background_color = match line.chars().next() {
+        Some('+') => config.plus_color,
+        Some('-') => config.minus_color,
+        _ => None,
+    };
+    let mut highlighter = HighlightLines::new(syntax, config.theme);
+    let ranges: Vec<(Style, &str)> = highlighter.highlight(&line, &config.syntax_set);
+    let mut final_ranges: Vec<(Style, &str)> = Vec::new();
+    let mut byte_offset = 0;
+    for (mut style, text) in ranges {
+        if let Some(background_color) = background_color {
+            style.background = background_color;
+        }
+        for (piece, is_emphasized) in split_by_emphasis(text, byte_offset, emphasized) {
+            let mut piece_style = style;
+            if is_emphasized {
+                piece_style.font_style |= FontStyle::BOLD;
+                piece_style.background = brighten(piece_style.background);
+            } else {
+                piece_style.foreground = dim(piece_style.foreground, piece_style.background);
+            }
+            final_ranges.push((piece_style, piece));
+        }
+        byte_offset += text.len();
+    }
+    output.push_str(&escape_ranges(&final_ranges, config));
+    pad_to_width(
+        line.chars().count(),
+        final_ranges.last().map(|(style, _)| *style),
+        config,
+        output,
+        gutter_prefix_width(gutter),
+    );
+}
+
+/// Width, in characters, occupied by the gutter `paint_line`/
+/// `paint_line_with_emphasis` already wrote before the line content, for
+/// use by `pad_to_width`.
+fn gutter_prefix_width(gutter: Option<(Option<usize>, Option<usize>)>) -> usize {
+    if gutter.is_some() {
+        GUTTER_WIDTH
+    } else {
+        0
+    }
+}
+
+/// Split `text` — a fragment of the full line starting at byte `offset` —
+/// into `(piece, is_emphasized)` pairs according to `emphasized`, a list
+/// of non-overlapping byte ranges (in terms of the full line) to mark.
+fn split_by_emphasis<'a>(
+    text: &'a str,
+    offset: usize,
+    emphasized: &[(usize, usize)],
+) -> Vec<(&'a str, bool)> {
+    let text_start = offset;
+    let text_end = offset + text.len();
+    let mut cuts: Vec<usize> = vec![0, text.len()];
+    for &(start, end) in emphasized {
+        if start > text_start && start < text_end {
+            cuts.push(start - text_start);
+        }
+        if end > text_start && end < text_end {
+            cuts.push(end - text_start);
+        }
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+    cuts.windows(2)
+        .filter(|w| w[0] != w[1])
+        .map(|w| {
+            let piece = &text[w[0]..w[1]];
+            let mid = text_start + w[0];
+            let is_emphasized = emphasized.iter().any(|&(s, e)| mid >= s && mid < e);
+            (piece, is_emphasized)
+        })
+        .collect()
+}
+
+/// Width, in characters, of each of the two number fields in the
+/// line-number gutter rendered by `render_gutter`.
+const GUTTER_FIELD_WIDTH: usize = 4;
+
+/// Total visible width (ignoring escape codes) of the gutter rendered by
+/// `render_gutter`, for use by `gutter_prefix_width` to fold the gutter
+/// into `pad_to_width`'s padding target.
+const GUTTER_WIDTH: usize = GUTTER_FIELD_WIDTH * 2 + 2;
+
+/// Render the `--line-numbers` gutter: the old and new line numbers,
+/// dimmed, in two right-aligned fields, blank where a side has no line.
+fn render_gutter(old: Option<usize>, new: Option<usize>) -> String {
+    let field = |n: Option<usize>| match n {
+        Some(n) => format!("{:>width$}", n, width = GUTTER_FIELD_WIDTH),
+        None => " ".repeat(GUTTER_FIELD_WIDTH),
+    };
+    format!("\x1b[2m{} {}\x1b[0m ", field(old), field(new))
+}
+
+/// Brighten a background color to make an emphasized span stand out
+/// against the surrounding, unemphasized part of the same line.
+fn brighten(color: Color) -> Color {
+    let lift = |c: u8| c.saturating_add((255 - c) / 2);
+    Color {
+        r: lift(color.r),
+        g: lift(color.g),
+        b: lift(color.b),
+        a: color.a,
+    }
+}
+
+/// Dim a foreground color by blending it halfway towards the background
+/// it sits on, so unemphasized text recedes relative to an emphasized
+/// span without needing a color of its own.
+fn dim(foreground: Color, background: Color) -> Color {
+    let blend = |fg: u8, bg: u8| ((u16::from(fg) + u16::from(bg)) / 2) as u8;
+    Color {
+        r: blend(foreground.r, background.r),
+        g: blend(foreground.g, background.g),
+        b: blend(foreground.b, background.b),
+        a: foreground.a,
+    }
+}
+
+/// Extend `output` with blank, styled padding so the painted line, plus
+/// any gutter already written before it (`prefix_width`), together reach
+/// `config.width` columns, if set. This keeps `--width` describing the
+/// total width of each rendered column even when `--line-numbers` has
+/// added a gutter in front of the content.
+fn pad_to_width(length: usize, style: Option<Style>, config: &Config, output: &mut String, prefix_width: usize) {
+    if let Some(width) = config.width {
+        let total = prefix_width + length;
+        if total < width {
+            let pad_style = style.unwrap_or_default();
+            let padding = " ".repeat(width - total);
+            output.push_str(&escape_ranges(&[(pad_style, padding.as_str())], config));
+        }
+    }
+}
+
+/// Render `ranges` as terminal escape codes, using full 24-bit color when
+/// the terminal supports it and degrading each color to the nearest
+/// xterm 256-color index otherwise.
+fn escape_ranges(ranges: &[(Style, &str)], config: &Config) -> String {
+    if config.true_color {
+        as_24_bit_terminal_escaped(ranges, false)
+    } else {
+        as_256_color_terminal_escaped(ranges)
+    }
+}
+
+/// Like `syntect::util::as_24_bit_terminal_escaped`, but quantizes each
+/// foreground/background color to the xterm 256-color palette and emits
+/// `38;5;N`/`48;5;N` codes, for terminals that lack true-color support.
+fn as_256_color_terminal_escaped(ranges: &[(Style, &str)]) -> String {
+    let mut escaped = String::new();
+    for (style, text) in ranges {
+        let bold = if style.font_style.contains(FontStyle::BOLD) { "1;" } else { "22;" };
+        escaped.push_str(&format!(
+            "\x1b[{}38;5;{};48;5;{}m{}",
+            bold,
+            nearest_256_color(style.foreground),
+            nearest_256_color(style.background),
+            text
+        ));
+    }
+    escaped.push_str("\x1b[0m");
+    escaped
+}
+
+/// Map a 24-bit RGB color to a nearby color in the xterm 256-color
+/// palette. The 16 base colors are left to the terminal's own mapping.
+/// An exact gray goes to the nearest step of the 24-step grayscale ramp
+/// (near-black and near-white to the color cube's black and white);
+/// any other color goes to the nearest entry of the 6x6x6 color cube.
+/// Grays are not compared against the cube's own gray entries, nor
+/// other colors against the grayscale ramp.
+fn nearest_256_color(color: Color) -> u8 {
+    if color.r == color.g && color.g == color.b {
+        return match color.r {
+            0..=7 => 16,
+            248..=255 => 231,
+            gray => 232 + ((u16::from(gray) - 3) / 10).min(23) as u8,
+        };
+    }
+    // Cube levels are 0, 95, 135, 175, 215, 255; round to the nearest one.
+    let channel = |c: u8| match c {
+        0..=47 => 0,
+        48..=114 => 1,
+        c => (u16::from(c) - 35) / 40,
+    };
+    16 + 36 * channel(color.r) as u8 + 6 * channel(color.g) as u8 + channel(color.b) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 255 }
+    }
+
+    #[test]
+    fn black_maps_to_the_color_cube_black() {
+        assert_eq!(nearest_256_color(rgb(0, 0, 0)), 16);
+    }
+
+    #[test]
+    fn white_maps_to_the_color_cube_white() {
+        assert_eq!(nearest_256_color(rgb(255, 255, 255)), 231);
+    }
+
+    #[test]
+    fn mid_gray_maps_into_the_grayscale_ramp() {
+        let color = nearest_256_color(rgb(128, 128, 128));
+        assert!((232..=255).contains(&color));
+    }
+
+    #[test]
+    fn pure_red_maps_into_the_color_cube() {
+        assert_eq!(nearest_256_color(rgb(255, 0, 0)), 16 + 36 * 5);
+    }
+}
@@ -0,0 +1,93 @@
+/// Given a line of the form `diff --git a/src/main.rs b/src/main.rs`,
+/// return the file extension of the file being diffed, e.g. `Some("rs")`.
+pub fn get_file_extension_from_diff_line(line: &str) -> Option<&str> {
+    line.split(' ').last().and_then(get_file_extension)
+}
+
+fn get_file_extension(path: &str) -> Option<&str> {
+    match path.rfind('.') {
+        Some(dot_index) if dot_index < path.len() - 1 => Some(&path[dot_index + 1..]),
+        _ => None,
+    }
+}
+
+/// Given a line of the form `diff --git a/src/main.rs b/src/main.rs`,
+/// return the bare file name being diffed, e.g. `Some("main.rs")`. Used
+/// to recognize extensionless files such as `Makefile` or `Dockerfile`
+/// whose syntax is keyed on their full name rather than an extension.
+pub fn get_file_name_from_diff_line(line: &str) -> Option<&str> {
+    let path = line.split(' ').last()?;
+    Some(path.rsplit('/').next().unwrap_or(path))
+}
+
+/// Well-known extensionless file names, mapped to the extension under
+/// which their closest available syntax is registered in syntect's
+/// bundled syntax set. `Dockerfile` has no dedicated grammar there, so
+/// it is approximated with shell syntax, which covers its `RUN`/`CMD`
+/// instruction bodies reasonably well even though it misses the
+/// directive keywords (`FROM`, `COPY`, ...).
+const KNOWN_EXTENSIONLESS_FILES: &[(&str, &str)] = &[("dockerfile", "sh"), ("makefile", "Makefile")];
+
+/// Look up the syntax-set extension to try for a known extensionless
+/// file name such as `Dockerfile`, matched case-insensitively since
+/// these files are conventionally capitalized but not always.
+pub fn lookup_known_extensionless_file(file_name: &str) -> Option<&'static str> {
+    let file_name = file_name.to_lowercase();
+    KNOWN_EXTENSIONLESS_FILES
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, extension)| *extension)
+}
+
+/// Parse a hunk header of the form `@@ -a,b +c,d @@ ...` and return the
+/// starting line number of the old file and of the new file,
+/// `(old_start, new_start)`. The `,count` part of either range is
+/// optional in real diff output (it is omitted when the count is 1).
+pub fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let mut parts = line.trim_start_matches("@@ ").split(" @@").next()?.split_whitespace();
+    let old_start = parse_hunk_range(parts.next()?, '-')?;
+    let new_start = parse_hunk_range(parts.next()?, '+')?;
+    Some((old_start, new_start))
+}
+
+fn parse_hunk_range(field: &str, sign: char) -> Option<usize> {
+    field.strip_prefix(sign)?.split(',').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_dockerfile_case_insensitively() {
+        assert_eq!(lookup_known_extensionless_file("Dockerfile"), Some("sh"));
+        assert_eq!(lookup_known_extensionless_file("dockerfile"), Some("sh"));
+    }
+
+    #[test]
+    fn looks_up_makefile() {
+        assert_eq!(lookup_known_extensionless_file("Makefile"), Some("Makefile"));
+    }
+
+    #[test]
+    fn unknown_file_name_is_not_found() {
+        assert_eq!(lookup_known_extensionless_file("main.rs"), None);
+    }
+
+    #[test]
+    fn parses_hunk_header_with_explicit_counts() {
+        assert_eq!(parse_hunk_header("@@ -12,5 +12,7 @@ fn foo() {"), Some((12, 12)));
+    }
+
+    #[test]
+    fn parses_hunk_header_with_omitted_counts() {
+        // Real diff output omits `,count` on a side when its count is 1.
+        assert_eq!(parse_hunk_header("@@ -12 +12,2 @@"), Some((12, 12)));
+        assert_eq!(parse_hunk_header("@@ -1,2 +1 @@"), Some((1, 1)));
+    }
+
+    #[test]
+    fn rejects_malformed_hunk_header() {
+        assert_eq!(parse_hunk_header("not a hunk header"), None);
+    }
+}
@@ -0,0 +1,117 @@
+extern crate libc;
+
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
+/// Guess whether the terminal has a light or dark background. Tries the
+/// `COLORFGBG` environment variable (set by many terminal emulators)
+/// first, since it is instant and needs no I/O, then falls back to
+/// querying the terminal directly with an OSC 11 escape sequence.
+/// Returns `None` if neither source yields an answer, e.g. when the
+/// process has no controlling terminal (`/dev/tty` cannot be opened).
+pub fn detect_background() -> Option<Background> {
+    detect_background_from_colorfgbg().or_else(detect_background_from_osc11)
+}
+
+fn detect_background_from_colorfgbg() -> Option<Background> {
+    let colorfgbg = env::var("COLORFGBG").ok()?;
+    let bg_index: u8 = colorfgbg.rsplit(';').next()?.parse().ok()?;
+    Some(match bg_index {
+        0..=6 | 8 => Background::Dark,
+        _ => Background::Light,
+    })
+}
+
+/// Ask the terminal for its background color via `OSC 11 ; ? BEL` and
+/// read the `rgb:rrrr/gggg/bbbb` reply it writes back to the tty. Gives
+/// up after a short timeout, since most terminals that don't support the
+/// query will simply never reply.
+fn detect_background_from_osc11() -> Option<Background> {
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    tty.write_all(b"\x1b]11;?\x07").ok()?;
+
+    if !wait_readable(&tty, Duration::from_millis(100)) {
+        return None;
+    }
+    let mut buf = [0u8; 64];
+    let n = tty.read(&mut buf).ok()?;
+    parse_osc11_reply(&String::from_utf8_lossy(&buf[..n]))
+}
+
+/// Block until `file` has data available to read or `timeout` elapses,
+/// returning whether it became readable. Used so that a terminal which
+/// never answers our OSC 11 query can't block the read itself (and with
+/// it, this call) indefinitely — unlike bounding only the receiving end
+/// of a channel fed by a detached reader thread, which still leaks a
+/// thread blocked on the tty for the rest of the process's life.
+fn wait_readable(file: &impl AsRawFd, timeout: Duration) -> bool {
+    let mut poll_fd = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+    let ready = unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) };
+    ready > 0 && poll_fd.revents & libc::POLLIN != 0
+}
+
+fn parse_osc11_reply(reply: &str) -> Option<Background> {
+    let rgb = reply.split("rgb:").nth(1)?;
+    let mut channels = rgb.splitn(3, '/');
+    let parse_channel = |s: &str| u32::from_str_radix(&s[..s.len().min(2)], 16).ok();
+    let r = parse_channel(channels.next()?)?;
+    let g = parse_channel(channels.next()?)?;
+    let b = parse_channel(channels.next()?)?;
+    // Perceived luminance, using the ITU-R BT.601 luma weights.
+    let luminance = (299 * r + 587 * g + 114 * b) / 1000;
+    Some(if luminance < 128 { Background::Dark } else { Background::Light })
+}
+
+/// Whether the terminal has advertised 24-bit ("truecolor") support via
+/// the `COLORTERM` environment variable.
+pub fn supports_truecolor() -> bool {
+    match env::var("COLORTERM") {
+        Ok(value) => value == "truecolor" || value == "24bit",
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dark_background_reply() {
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:1111/1111/1111\x07"), Some(Background::Dark));
+    }
+
+    #[test]
+    fn parses_light_background_reply() {
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:ffff/ffff/ffff\x07"), Some(Background::Light));
+    }
+
+    #[test]
+    fn parses_reply_with_short_channel_values() {
+        // Some terminals reply with 2-digit rather than 4-digit channels.
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:00/00/00\x07"), Some(Background::Dark));
+    }
+
+    #[test]
+    fn rejects_reply_without_rgb_prefix() {
+        assert_eq!(parse_osc11_reply("\x1b]11;garbage\x07"), None);
+    }
+
+    #[test]
+    fn rejects_reply_missing_a_channel() {
+        assert_eq!(parse_osc11_reply("\x1b]11;rgb:1111/1111\x07"), None);
+    }
+}